@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use target_lexicon::{Endianness, PointerWidth};
+
+use crate::types::{FlatTomlValue, SupportedPlatform, SupportedTarget};
+
+/// A parsed `cfg(...)` predicate, as used to gate a fel4 manifest's
+/// `properties` entries on the resolved target/platform, e.g.
+/// `cfg(target_arch = "arm")` or
+/// `cfg(all(target_os = "sel4", target_pointer_width = "64"))`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CfgPredicate {
+    All(Vec<CfgPredicate>),
+    Any(Vec<CfgPredicate>),
+    Not(Box<CfgPredicate>),
+    Predicate { key: String, value: String },
+}
+
+impl CfgPredicate {
+    /// Parse a `cfg(...)` string into a predicate tree.
+    pub fn parse(input: &str) -> Result<CfgPredicate, String> {
+        let trimmed = input.trim();
+        let inner = trimmed
+            .strip_prefix("cfg(")
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or_else(|| format!("expected a `cfg(...)` expression, got `{}`", trimmed))?;
+        parse_expr(inner)
+    }
+
+    /// Evaluate this predicate against the resolved `TargetInfo`, requiring
+    /// `All` children to all be true, `Any` children to have at least one
+    /// true, and `Not` to negate its child. A leaf referencing a key
+    /// `TargetInfo` doesn't recognize (e.g. a typo'd `target_ach`) is an
+    /// error rather than a silent `false`.
+    pub fn eval(&self, info: &TargetInfo) -> Result<bool, String> {
+        match self {
+            CfgPredicate::All(preds) => {
+                let results = preds
+                    .iter()
+                    .map(|p| p.eval(info))
+                    .collect::<Result<Vec<bool>, String>>()?;
+                Ok(results.into_iter().all(|b| b))
+            }
+            CfgPredicate::Any(preds) => {
+                let results = preds
+                    .iter()
+                    .map(|p| p.eval(info))
+                    .collect::<Result<Vec<bool>, String>>()?;
+                Ok(results.into_iter().any(|b| b))
+            }
+            CfgPredicate::Not(pred) => Ok(!pred.eval(info)?),
+            CfgPredicate::Predicate { key, value } => {
+                let actual = info
+                    .get(key)
+                    .ok_or_else(|| format!("unrecognized cfg key `{}`", key))?;
+                Ok(actual == value)
+            }
+        }
+    }
+}
+
+fn parse_expr(input: &str) -> Result<CfgPredicate, String> {
+    let trimmed = input.trim();
+    if let Some(inner) = strip_call(trimmed, "all") {
+        return Ok(CfgPredicate::All(parse_list(inner)?));
+    }
+    if let Some(inner) = strip_call(trimmed, "any") {
+        return Ok(CfgPredicate::Any(parse_list(inner)?));
+    }
+    if let Some(inner) = strip_call(trimmed, "not") {
+        let mut preds = parse_list(inner)?;
+        if preds.len() != 1 {
+            return Err(format!(
+                "`not(...)` takes exactly one predicate, got `{}`",
+                trimmed
+            ));
+        }
+        return Ok(CfgPredicate::Not(Box::new(preds.remove(0))));
+    }
+    parse_leaf(trimmed)
+}
+
+fn strip_call<'a>(input: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = format!("{}(", name);
+    input
+        .strip_prefix(prefix.as_str())
+        .and_then(|s| s.strip_suffix(')'))
+}
+
+fn parse_list(input: &str) -> Result<Vec<CfgPredicate>, String> {
+    split_top_level(input).into_iter().map(parse_expr).collect()
+}
+
+/// Split a comma-separated argument list on its top-level commas, ignoring
+/// commas nested inside parentheses.
+fn split_top_level(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(input[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = input[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+fn parse_leaf(input: &str) -> Result<CfgPredicate, String> {
+    let (key, value) = input
+        .split_once('=')
+        .ok_or_else(|| format!("expected `key = \"value\"`, got `{}`", input))?;
+    let key = key.trim().to_string();
+    let value = value.trim().trim_matches('"').to_string();
+    Ok(CfgPredicate::Predicate { key, value })
+}
+
+/// The resolved target/platform facts a `CfgPredicate` is evaluated
+/// against, mirroring the `target_arch`/`target_os`/`target_pointer_width`/
+/// `target_endian` keys `rustc` exposes via `cfg(...)`, plus a synthetic
+/// `platform` key for the fel4 platform.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetInfo {
+    pub target_arch: String,
+    pub target_os: String,
+    pub target_pointer_width: String,
+    pub target_endian: String,
+    pub platform: String,
+}
+
+impl TargetInfo {
+    pub fn new(target: SupportedTarget, platform: SupportedPlatform) -> Self {
+        // `rustc`'s `target_arch` values don't line up 1:1 with
+        // target-lexicon's `Display` impl (e.g. `Armv7` renders as
+        // `"armv7"`, not the `"arm"` rustc reports), so map from the fel4
+        // target directly rather than through the triple's architecture.
+        let target_arch = match target {
+            SupportedTarget::X8664Sel4Fel4 => "x86_64",
+            SupportedTarget::Armv7Sel4Fel4 => "arm",
+            SupportedTarget::Aarch64Sel4Fel4 => "aarch64",
+            SupportedTarget::Riscv32Sel4Fel4 => "riscv32",
+            SupportedTarget::Riscv64Sel4Fel4 => "riscv64",
+        };
+        let arch = target.triple().architecture;
+        let pointer_width = match arch.pointer_width() {
+            Ok(PointerWidth::U16) => "16",
+            Ok(PointerWidth::U32) => "32",
+            Ok(PointerWidth::U64) => "64",
+            Err(()) => "unknown",
+        };
+        let endian = match arch.endianness() {
+            Ok(Endianness::Little) => "little",
+            Ok(Endianness::Big) => "big",
+            Err(()) => "unknown",
+        };
+        TargetInfo {
+            target_arch: target_arch.to_string(),
+            target_os: "sel4".to_string(),
+            target_pointer_width: pointer_width.to_string(),
+            target_endian: endian.to_string(),
+            platform: platform.full_name().to_string(),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        match key {
+            "target_arch" => Some(&self.target_arch),
+            "target_os" => Some(&self.target_os),
+            "target_pointer_width" => Some(&self.target_pointer_width),
+            "target_endian" => Some(&self.target_endian),
+            "platform" => Some(&self.platform),
+            _ => None,
+        }
+    }
+}
+
+/// An error produced while resolving `cfg(...)`-gated property blocks into
+/// a single flattened property set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CfgResolutionError {
+    /// Two or more matching `cfg(...)` blocks set the same property key.
+    ConflictingKey { key: String },
+    /// A `cfg(...)` predicate couldn't be evaluated, e.g. it referenced a
+    /// key `TargetInfo` doesn't recognize.
+    InvalidPredicate(String),
+}
+
+impl Display for CfgResolutionError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match self {
+            CfgResolutionError::ConflictingKey { key } => write!(
+                f,
+                "property `{}` is set by more than one matching cfg(...) block",
+                key
+            ),
+            CfgResolutionError::InvalidPredicate(reason) => {
+                write!(f, "invalid cfg(...) predicate: {}", reason)
+            }
+        }
+    }
+}
+
+/// Resolve a manifest's `cfg(...)`-gated property blocks against `info`,
+/// dropping any block whose predicate evaluates false and flattening the
+/// rest into a single property set. Matching blocks that set the same key
+/// are a resolution error rather than a silent overwrite, as is a
+/// predicate that references an unrecognized key.
+pub fn resolve_cfg_properties(
+    blocks: &[(CfgPredicate, HashMap<String, FlatTomlValue>)],
+    info: &TargetInfo,
+) -> Result<BTreeMap<String, FlatTomlValue>, CfgResolutionError> {
+    let mut resolved = BTreeMap::new();
+    for (predicate, properties) in blocks {
+        let matches = predicate
+            .eval(info)
+            .map_err(CfgResolutionError::InvalidPredicate)?;
+        if !matches {
+            continue;
+        }
+        for (key, value) in properties {
+            if resolved.contains_key(key) {
+                return Err(CfgResolutionError::ConflictingKey { key: key.clone() });
+            }
+            resolved.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info_for(target: SupportedTarget, platform: SupportedPlatform) -> TargetInfo {
+        TargetInfo::new(target, platform)
+    }
+
+    #[test]
+    fn cfg_round_trip_per_arch() {
+        let cases = [
+            (SupportedTarget::X8664Sel4Fel4, "x86_64"),
+            (SupportedTarget::Armv7Sel4Fel4, "arm"),
+            (SupportedTarget::Aarch64Sel4Fel4, "aarch64"),
+            (SupportedTarget::Riscv32Sel4Fel4, "riscv32"),
+            (SupportedTarget::Riscv64Sel4Fel4, "riscv64"),
+        ];
+        for (target, arch) in cases {
+            let info = info_for(target, SupportedPlatform::PC99);
+            let predicate =
+                CfgPredicate::parse(&format!("cfg(target_arch = \"{}\")", arch)).unwrap();
+            assert_eq!(predicate.eval(&info), Ok(true));
+        }
+    }
+
+    #[test]
+    fn flagship_example_matches_64_bit_sel4_targets() {
+        let info = info_for(SupportedTarget::X8664Sel4Fel4, SupportedPlatform::PC99);
+        let predicate =
+            CfgPredicate::parse("cfg(all(target_os = \"sel4\", target_pointer_width = \"64\"))")
+                .unwrap();
+        assert_eq!(predicate.eval(&info), Ok(true));
+    }
+
+    #[test]
+    fn unrecognized_key_is_an_error_not_a_silent_false() {
+        let info = info_for(SupportedTarget::Armv7Sel4Fel4, SupportedPlatform::Sabre);
+        let predicate = CfgPredicate::parse("cfg(target_ach = \"arm\")").unwrap();
+        assert!(predicate.eval(&info).is_err());
+    }
+
+    #[test]
+    fn resolve_cfg_properties_surfaces_invalid_predicate_as_error() {
+        let info = info_for(SupportedTarget::Armv7Sel4Fel4, SupportedPlatform::Sabre);
+        let mut properties = HashMap::new();
+        properties.insert("foo".to_string(), FlatTomlValue::Boolean(true));
+        let blocks = vec![(
+            CfgPredicate::parse("cfg(target_ach = \"arm\")").unwrap(),
+            properties,
+        )];
+        assert_eq!(
+            resolve_cfg_properties(&blocks, &info),
+            Err(CfgResolutionError::InvalidPredicate(
+                "unrecognized cfg key `target_ach`".to_string()
+            ))
+        );
+    }
+}
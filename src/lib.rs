@@ -0,0 +1,8 @@
+pub mod cfg;
+pub mod types;
+
+pub use cfg::{resolve_cfg_properties, CfgPredicate, CfgResolutionError, TargetInfo};
+pub use types::{
+    resolve_build_profile_properties, BuildProfile, CustomBuildProfile, Fel4Config,
+    FlatTomlProperty, FlatTomlValue, SupportedPlatform, SupportedTarget,
+};
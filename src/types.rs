@@ -1,23 +1,81 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
 use std::fmt::{Display, Error as FmtError, Formatter};
 use std::str::FromStr;
+use serde::de::{self, Deserializer};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use target_lexicon::{
+    Aarch64Architecture, Architecture, ArmArchitecture, BinaryFormat, Endianness, Environment,
+    OperatingSystem, PointerWidth, Riscv32Architecture, Riscv64Architecture, Triple, Vendor,
+};
 use toml;
 
 /// Fel4 configuration for a particular target, platform, and build profile
 /// tuple resolved from a FullFel4Target
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Fel4Config {
     pub artifact_path: String,
     pub target_specs_path: String,
     pub target: SupportedTarget,
     pub platform: SupportedPlatform,
     pub build_profile: BuildProfile,
-    pub properties: HashMap<String, FlatTomlValue>,
+    #[serde(serialize_with = "serialize_ordered_properties")]
+    pub properties: BTreeMap<String, FlatTomlValue>,
+}
+
+impl Fel4Config {
+    /// The `target-lexicon` architecture backing this config's resolved
+    /// `SupportedTarget`.
+    pub fn architecture(&self) -> Architecture {
+        self.target.triple().architecture
+    }
+
+    /// The pointer width (32- or 64-bit) of this config's resolved target.
+    pub fn pointer_width(&self) -> PointerWidth {
+        self.architecture()
+            .pointer_width()
+            .expect("every supported fel4 target architecture has a defined pointer width")
+    }
+
+    /// The byte order (big- or little-endian) of this config's resolved
+    /// target.
+    pub fn endianness(&self) -> Endianness {
+        self.architecture()
+            .endianness()
+            .expect("every supported fel4 target architecture has a defined endianness")
+    }
+
+    /// Serialize this resolved config back out to a TOML manifest string.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    /// Parse a previously-serialized `Fel4Config` back out of TOML.
+    pub fn from_toml(s: &str) -> Result<Fel4Config, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    /// Look up a property and return its elements if it's an array.
+    pub fn array_property(&self, key: &str) -> Option<&[FlatTomlValue]> {
+        match self.properties.get(key)? {
+            FlatTomlValue::Array(items) => Some(items.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Look up a property and return its entries if it's a nested table.
+    pub fn table_property(&self, key: &str) -> Option<&BTreeMap<String, FlatTomlValue>> {
+        match self.properties.get(key)? {
+            FlatTomlValue::Table(table) => Some(table),
+            _ => None,
+        }
+    }
 }
 
 /// A single toml key-value pair where the value only includes non-nestable
 /// structures
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct FlatTomlProperty {
     pub name: String,
     pub value: FlatTomlValue,
@@ -29,7 +87,11 @@ impl FlatTomlProperty {
     }
 }
 
-/// A subset of `toml::Value` that only includes non-nestable structures
+/// A subset of `toml::Value`: scalars, homogeneously-typed arrays, and
+/// tables nested up to `FlatTomlValue::MAX_TABLE_DEPTH` levels deep. This
+/// bound keeps `properties` representable without arbitrary recursion
+/// while still covering seL4 settings like per-CPU arrays or nested region
+/// descriptors.
 #[derive(PartialEq, Clone, Debug)]
 pub enum FlatTomlValue {
     /// Represents a TOML string
@@ -42,6 +104,170 @@ pub enum FlatTomlValue {
     Boolean(bool),
     /// Represents a TOML datetime,
     Datetime(toml::value::Datetime),
+    /// Represents a homogeneously-typed TOML array
+    Array(Vec<FlatTomlValue>),
+    /// Represents a nested TOML table, bounded to `MAX_TABLE_DEPTH` levels.
+    /// Keyed by a `BTreeMap` (rather than a `HashMap`) so serialization
+    /// order is deterministic. `toml` also requires all of a table's
+    /// scalar keys to be emitted before any of its sub-table keys, which
+    /// alphabetical order alone doesn't guarantee; the `Serialize` impl
+    /// re-sorts entries so sub-tables come last.
+    Table(BTreeMap<String, FlatTomlValue>),
+}
+
+impl From<&FlatTomlValue> for toml::Value {
+    fn from(value: &FlatTomlValue) -> Self {
+        match value {
+            FlatTomlValue::String(s) => toml::Value::String(s.clone()),
+            FlatTomlValue::Integer(i) => toml::Value::Integer(*i),
+            FlatTomlValue::Float(f) => toml::Value::Float(*f),
+            FlatTomlValue::Boolean(b) => toml::Value::Boolean(*b),
+            FlatTomlValue::Datetime(d) => toml::Value::Datetime(d.clone()),
+            FlatTomlValue::Array(items) => {
+                toml::Value::Array(items.iter().map(toml::Value::from).collect())
+            }
+            FlatTomlValue::Table(table) => toml::Value::Table(
+                table
+                    .iter()
+                    .map(|(k, v)| (k.clone(), toml::Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl TryFrom<toml::Value> for FlatTomlValue {
+    type Error = String;
+
+    fn try_from(value: toml::Value) -> Result<Self, Self::Error> {
+        FlatTomlValue::from_toml_value(value, 0)
+    }
+}
+
+impl FlatTomlValue {
+    /// The deepest a `Table` value may nest before conversion from
+    /// `toml::Value` is rejected.
+    pub const MAX_TABLE_DEPTH: usize = 4;
+
+    fn from_toml_value(value: toml::Value, depth: usize) -> Result<Self, String> {
+        match value {
+            toml::Value::String(s) => Ok(FlatTomlValue::String(s)),
+            toml::Value::Integer(i) => Ok(FlatTomlValue::Integer(i)),
+            toml::Value::Float(f) => Ok(FlatTomlValue::Float(f)),
+            toml::Value::Boolean(b) => Ok(FlatTomlValue::Boolean(b)),
+            toml::Value::Datetime(d) => Ok(FlatTomlValue::Datetime(d)),
+            toml::Value::Array(items) => {
+                let items = items
+                    .into_iter()
+                    .map(|item| FlatTomlValue::from_toml_value(item, depth))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Some(first) = items.first() {
+                    if items.iter().any(|item| !FlatTomlValue::same_shape(first, item)) {
+                        return Err("array properties must be homogeneously typed".to_string());
+                    }
+                }
+                Ok(FlatTomlValue::Array(items))
+            }
+            toml::Value::Table(table) => {
+                if depth >= FlatTomlValue::MAX_TABLE_DEPTH {
+                    return Err(format!(
+                        "table properties may nest at most {} levels deep",
+                        FlatTomlValue::MAX_TABLE_DEPTH
+                    ));
+                }
+                let table = table
+                    .into_iter()
+                    .map(|(k, v)| Ok((k, FlatTomlValue::from_toml_value(v, depth + 1)?)))
+                    .collect::<Result<BTreeMap<_, _>, String>>()?;
+                Ok(FlatTomlValue::Table(table))
+            }
+        }
+    }
+
+    /// Whether `a` and `b` have the same shape for the purposes of array
+    /// homogeneity: same variant, and for `Array` values, recursively the
+    /// same element shape too (so `[[1], ["a"]]` is rejected even though
+    /// both elements are themselves arrays).
+    fn same_shape(a: &FlatTomlValue, b: &FlatTomlValue) -> bool {
+        match (a, b) {
+            (FlatTomlValue::String(_), FlatTomlValue::String(_))
+            | (FlatTomlValue::Integer(_), FlatTomlValue::Integer(_))
+            | (FlatTomlValue::Float(_), FlatTomlValue::Float(_))
+            | (FlatTomlValue::Boolean(_), FlatTomlValue::Boolean(_))
+            | (FlatTomlValue::Datetime(_), FlatTomlValue::Datetime(_))
+            | (FlatTomlValue::Table(_), FlatTomlValue::Table(_)) => true,
+            (FlatTomlValue::Array(a_items), FlatTomlValue::Array(b_items)) => {
+                match (a_items.first(), b_items.first()) {
+                    (Some(a0), Some(b0)) => FlatTomlValue::same_shape(a0, b0),
+                    // An empty array's element type is unconstrained.
+                    _ => true,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Serialize for FlatTomlValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            // Table entries need the scalar-before-table reordering below,
+            // so they don't go through the generic `toml::Value`
+            // round-trip like the other variants do.
+            FlatTomlValue::Table(table) => serialize_ordered_properties(table, serializer),
+            other => toml::Value::from(other).serialize(serializer),
+        }
+    }
+}
+
+/// Serialize a property map with `toml`'s ordering requirement in mind:
+/// scalar/array keys must be emitted before any table keys. `BTreeMap`
+/// already makes iteration order deterministic, but its alphabetical
+/// ordering can still interleave tables with scalars (e.g. a key named
+/// `a_table` sorts before `b_scalar`), which `toml::to_string` rejects
+/// with "values must be emitted before tables". Stably re-sort so every
+/// table-like entry comes last instead.
+fn serialize_ordered_properties<S>(
+    properties: &BTreeMap<String, FlatTomlValue>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut entries: Vec<(&String, &FlatTomlValue)> = properties.iter().collect();
+    entries.sort_by(|(key_a, value_a), (key_b, value_b)| {
+        is_table_like(value_a)
+            .cmp(&is_table_like(value_b))
+            .then_with(|| key_a.cmp(key_b))
+    });
+    let mut map = serializer.serialize_map(Some(entries.len()))?;
+    for (key, value) in entries {
+        map.serialize_entry(key, value)?;
+    }
+    map.end()
+}
+
+/// Whether serializing `value` emits a TOML table (or array-of-tables)
+/// header, which must come after any sibling scalar keys.
+fn is_table_like(value: &FlatTomlValue) -> bool {
+    match value {
+        FlatTomlValue::Table(_) => true,
+        FlatTomlValue::Array(items) => items.first().map(is_table_like).unwrap_or(false),
+        _ => false,
+    }
+}
+
+impl<'de> Deserialize<'de> for FlatTomlValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = toml::Value::deserialize(deserializer)?;
+        FlatTomlValue::try_from(value).map_err(de::Error::custom)
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
@@ -49,11 +275,15 @@ pub enum SupportedTarget {
     X8664Sel4Fel4,
     Armv7Sel4Fel4,
     Aarch64Sel4Fel4,
+    Riscv32Sel4Fel4,
+    Riscv64Sel4Fel4,
 }
 
 const TARGET_X86_64_SEL4_FEL4: &str = "x86_64-sel4-fel4";
 const TARGET_ARMV7_SEL4_FEL4: &str = "armv7-sel4-fel4";
 const TARGET_AARCH64_SEL4_FEL4: &str = "aarch64-sel4-fel4";
+const TARGET_RISCV32_SEL4_FEL4: &str = "riscv32-sel4-fel4";
+const TARGET_RISCV64_SEL4_FEL4: &str = "riscv64-sel4-fel4";
 
 impl SupportedTarget {
     pub fn full_name(&self) -> &'static str {
@@ -61,6 +291,8 @@ impl SupportedTarget {
             SupportedTarget::X8664Sel4Fel4 => TARGET_X86_64_SEL4_FEL4,
             SupportedTarget::Armv7Sel4Fel4 => TARGET_ARMV7_SEL4_FEL4,
             SupportedTarget::Aarch64Sel4Fel4 => TARGET_AARCH64_SEL4_FEL4,
+            SupportedTarget::Riscv32Sel4Fel4 => TARGET_RISCV32_SEL4_FEL4,
+            SupportedTarget::Riscv64Sel4Fel4 => TARGET_RISCV64_SEL4_FEL4,
         }
     }
 
@@ -69,6 +301,8 @@ impl SupportedTarget {
             SupportedTarget::X8664Sel4Fel4,
             SupportedTarget::Armv7Sel4Fel4,
             SupportedTarget::Aarch64Sel4Fel4,
+            SupportedTarget::Riscv32Sel4Fel4,
+            SupportedTarget::Riscv64Sel4Fel4,
         ]
     }
 
@@ -78,6 +312,39 @@ impl SupportedTarget {
             .map(|t| t.full_name().into())
             .collect()
     }
+
+    /// The canonical `target-lexicon` triple backing this fel4 target.
+    ///
+    /// fel4 targets are custom Rust target-spec JSONs rather than triples
+    /// `target-lexicon` recognizes outright, so only the `architecture`
+    /// field (and anything derived from it, like pointer width and
+    /// endianness) is authoritative; vendor/operating-system/environment
+    /// are left `Unknown`.
+    pub fn triple(&self) -> Triple {
+        Triple {
+            architecture: self.architecture(),
+            vendor: Vendor::Unknown,
+            operating_system: OperatingSystem::Unknown,
+            environment: Environment::Unknown,
+            binary_format: BinaryFormat::Elf,
+        }
+    }
+
+    fn architecture(&self) -> Architecture {
+        match *self {
+            SupportedTarget::X8664Sel4Fel4 => Architecture::X86_64,
+            SupportedTarget::Armv7Sel4Fel4 => Architecture::Arm(ArmArchitecture::Armv7),
+            SupportedTarget::Aarch64Sel4Fel4 => {
+                Architecture::Aarch64(Aarch64Architecture::Aarch64)
+            }
+            SupportedTarget::Riscv32Sel4Fel4 => {
+                Architecture::Riscv32(Riscv32Architecture::Riscv32)
+            }
+            SupportedTarget::Riscv64Sel4Fel4 => {
+                Architecture::Riscv64(Riscv64Architecture::Riscv64)
+            }
+        }
+    }
 }
 
 impl Display for SupportedTarget {
@@ -94,11 +361,32 @@ impl FromStr for SupportedTarget {
             TARGET_X86_64_SEL4_FEL4 => Ok(SupportedTarget::X8664Sel4Fel4),
             TARGET_ARMV7_SEL4_FEL4 => Ok(SupportedTarget::Armv7Sel4Fel4),
             TARGET_AARCH64_SEL4_FEL4 => Ok(SupportedTarget::Aarch64Sel4Fel4),
+            TARGET_RISCV32_SEL4_FEL4 => Ok(SupportedTarget::Riscv32Sel4Fel4),
+            TARGET_RISCV64_SEL4_FEL4 => Ok(SupportedTarget::Riscv64Sel4Fel4),
             _ => Err(s.to_string()),
         }
     }
 }
 
+impl Serialize for SupportedTarget {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.full_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for SupportedTarget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        SupportedTarget::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum SupportedPlatform {
     PC99,
@@ -154,18 +442,69 @@ impl FromStr for SupportedPlatform {
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+impl Serialize for SupportedPlatform {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.full_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for SupportedPlatform {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        SupportedPlatform::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+/// A build profile, following Cargo's `[profile.*]` model: the built-in
+/// `Debug`/`Release` profiles are roots, and a manifest may also declare a
+/// `Custom` profile with its own name that inherits shared properties from
+/// another profile.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum BuildProfile {
     Debug,
     Release,
+    Custom(CustomBuildProfile),
+}
+
+/// A user-named build profile and the profile it inherits properties from.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CustomBuildProfile {
+    pub name: String,
+    pub inherits: Box<BuildProfile>,
 }
+
 const BUILD_PROFILE_DEBUG: &str = "debug";
 const BUILD_PROFILE_RELEASE: &str = "release";
 impl BuildProfile {
-    pub fn full_name(&self) -> &'static str {
-        match *self {
+    pub fn full_name(&self) -> &str {
+        match self {
             BuildProfile::Debug => BUILD_PROFILE_DEBUG,
             BuildProfile::Release => BUILD_PROFILE_RELEASE,
+            BuildProfile::Custom(custom) => custom.name.as_str(),
+        }
+    }
+
+    /// Construct a custom, named profile that inherits properties from
+    /// `inherits`.
+    pub fn custom(name: String, inherits: BuildProfile) -> BuildProfile {
+        BuildProfile::Custom(CustomBuildProfile {
+            name,
+            inherits: Box::new(inherits),
+        })
+    }
+
+    /// The profile this one inherits properties from, if any. The built-in
+    /// `Debug`/`Release` profiles are roots and never inherit.
+    pub fn inherits(&self) -> Option<&BuildProfile> {
+        match self {
+            BuildProfile::Custom(custom) => Some(&custom.inherits),
+            BuildProfile::Debug | BuildProfile::Release => None,
         }
     }
 
@@ -192,3 +531,224 @@ impl FromStr for BuildProfile {
         }
     }
 }
+
+impl Serialize for BuildProfile {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            // The built-in profiles round-trip as their bare name.
+            BuildProfile::Debug | BuildProfile::Release => {
+                serializer.serialize_str(self.full_name())
+            }
+            // A custom profile needs its `inherits` pointer preserved too,
+            // so it's emitted as a small table rather than a bare string.
+            BuildProfile::Custom(custom) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("name", &custom.name)?;
+                map.serialize_entry("inherits", &custom.inherits)?;
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BuildProfile {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BuildProfileVisitor;
+
+        impl<'de> de::Visitor<'de> for BuildProfileVisitor {
+            type Value = BuildProfile;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str(
+                    "a build profile name, or a table with `name`/`inherits` for a custom profile",
+                )
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                BuildProfile::from_str(v).map_err(de::Error::custom)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut name: Option<String> = None;
+                let mut inherits: Option<BuildProfile> = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "name" => name = Some(map.next_value()?),
+                        "inherits" => inherits = Some(map.next_value()?),
+                        other => {
+                            return Err(de::Error::unknown_field(other, &["name", "inherits"]))
+                        }
+                    }
+                }
+                let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
+                let inherits = inherits.ok_or_else(|| de::Error::missing_field("inherits"))?;
+                Ok(BuildProfile::custom(name, inherits))
+            }
+        }
+
+        deserializer.deserialize_any(BuildProfileVisitor)
+    }
+}
+
+/// Flatten `profile`'s declared properties by walking its `inherits` chain
+/// from the root profile down to `profile` itself, with each profile's own
+/// properties overriding same-named keys inherited from its parent.
+pub fn resolve_build_profile_properties(
+    profile: &BuildProfile,
+    declared: &HashMap<BuildProfile, HashMap<String, FlatTomlValue>>,
+) -> BTreeMap<String, FlatTomlValue> {
+    let mut chain = vec![profile];
+    let mut current = profile;
+    while let Some(parent) = current.inherits() {
+        chain.push(parent);
+        current = parent;
+    }
+
+    let mut resolved = BTreeMap::new();
+    for p in chain.into_iter().rev() {
+        if let Some(properties) = declared.get(p) {
+            for (key, value) in properties {
+                resolved.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_config() -> Fel4Config {
+        let mut properties = BTreeMap::new();
+        properties.insert("int_val".to_string(), FlatTomlValue::Integer(42));
+        properties.insert("float_val".to_string(), FlatTomlValue::Float(1.5));
+        Fel4Config {
+            artifact_path: "artifact".to_string(),
+            target_specs_path: "target_specs".to_string(),
+            target: SupportedTarget::X8664Sel4Fel4,
+            platform: SupportedPlatform::PC99,
+            build_profile: BuildProfile::Debug,
+            properties,
+        }
+    }
+
+    #[test]
+    fn flat_toml_value_round_trips_integer_vs_float_vs_datetime() {
+        let int_value = FlatTomlValue::Integer(7);
+        let float_value = FlatTomlValue::Float(7.0);
+        let datetime_value =
+            FlatTomlValue::Datetime("1979-05-27T07:32:00Z".parse().unwrap());
+
+        assert_eq!(toml::Value::from(&int_value), toml::Value::Integer(7));
+        assert_eq!(toml::Value::from(&float_value), toml::Value::Float(7.0));
+
+        assert_eq!(
+            FlatTomlValue::try_from(toml::Value::Integer(7)).unwrap(),
+            int_value
+        );
+        assert_eq!(
+            FlatTomlValue::try_from(toml::Value::Float(7.0)).unwrap(),
+            float_value
+        );
+        assert_eq!(
+            FlatTomlValue::try_from(toml::Value::from(&datetime_value)).unwrap(),
+            datetime_value
+        );
+    }
+
+    #[test]
+    fn fel4_config_round_trips_through_toml() {
+        let config = sample_config();
+        let toml_str = config.to_toml().unwrap();
+        let parsed = Fel4Config::from_toml(&toml_str).unwrap();
+        assert_eq!(config, parsed);
+    }
+
+    #[test]
+    fn custom_build_profile_round_trips_through_toml() {
+        let profile = BuildProfile::custom("bench".to_string(), BuildProfile::Release);
+        let toml_str = toml::to_string(&profile).unwrap();
+        let parsed: BuildProfile = toml::from_str(&toml_str).unwrap();
+        assert_eq!(profile, parsed);
+
+        let mut config = sample_config();
+        config.build_profile = profile;
+        let toml_str = config.to_toml().unwrap();
+        let parsed = Fel4Config::from_toml(&toml_str).unwrap();
+        assert_eq!(config, parsed);
+    }
+
+    #[test]
+    fn resolve_build_profile_properties_merges_inherits_chain_child_wins() {
+        let base = BuildProfile::Release;
+        let bench = BuildProfile::custom("bench".to_string(), base.clone());
+
+        let mut declared = HashMap::new();
+        let mut base_properties = HashMap::new();
+        base_properties.insert("opt_level".to_string(), FlatTomlValue::Integer(2));
+        base_properties.insert("lto".to_string(), FlatTomlValue::Boolean(false));
+        declared.insert(base, base_properties);
+
+        let mut bench_properties = HashMap::new();
+        bench_properties.insert("lto".to_string(), FlatTomlValue::Boolean(true));
+        declared.insert(bench.clone(), bench_properties);
+
+        let resolved = resolve_build_profile_properties(&bench, &declared);
+        assert_eq!(resolved.get("opt_level"), Some(&FlatTomlValue::Integer(2)));
+        assert_eq!(resolved.get("lto"), Some(&FlatTomlValue::Boolean(true)));
+    }
+
+    #[test]
+    fn homogeneous_arrays_are_accepted() {
+        let value = toml::Value::Array(vec![toml::Value::Integer(1), toml::Value::Integer(2)]);
+        assert_eq!(
+            FlatTomlValue::try_from(value).unwrap(),
+            FlatTomlValue::Array(vec![FlatTomlValue::Integer(1), FlatTomlValue::Integer(2)])
+        );
+    }
+
+    #[test]
+    fn mixed_type_arrays_are_rejected() {
+        let value = toml::Value::Array(vec![
+            toml::Value::Integer(1),
+            toml::Value::String("a".to_string()),
+        ]);
+        assert!(FlatTomlValue::try_from(value).is_err());
+    }
+
+    #[test]
+    fn arrays_of_arrays_with_mismatched_element_types_are_rejected() {
+        // Both elements are themselves arrays, so a shallow discriminant
+        // check alone would wrongly call this homogeneous.
+        let value = toml::Value::Array(vec![
+            toml::Value::Array(vec![toml::Value::Integer(1)]),
+            toml::Value::Array(vec![toml::Value::String("a".to_string())]),
+        ]);
+        assert!(FlatTomlValue::try_from(value).is_err());
+    }
+
+    #[test]
+    fn table_property_accessor_returns_nested_table() {
+        let mut nested = BTreeMap::new();
+        nested.insert("cpu0".to_string(), FlatTomlValue::Integer(0));
+        let mut config = sample_config();
+        config
+            .properties
+            .insert("regions".to_string(), FlatTomlValue::Table(nested.clone()));
+        assert_eq!(config.table_property("regions"), Some(&nested));
+        assert_eq!(config.table_property("int_val"), None);
+    }
+}